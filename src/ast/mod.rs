@@ -0,0 +1,105 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum InfixOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    IntDiv,
+    Pow,
+    Shl,
+    Shr,
+    BitAnd,
+    BitXor,
+    BitOr,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Eq,
+    NotEq,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrefixOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignOp {
+    Assign,
+    AddAssign,
+    SubAssign,
+    MulAssign,
+    DivAssign,
+    ModAssign,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    IntegerLiteral(i64),
+    StringLiteral(String),
+    BooleanLiteral(bool),
+    Null,
+    Identifier(String),
+    ArrayLiteral(Vec<Expr>),
+    HashLiteral(Vec<(Expr, Expr)>),
+    Range {
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        inclusive: bool,
+    },
+    Prefix {
+        op: PrefixOp,
+        right: Box<Expr>,
+    },
+    Infix {
+        op: InfixOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Index {
+        left: Box<Expr>,
+        index: Box<Expr>,
+    },
+    Assign {
+        op: AssignOp,
+        target: Box<Expr>,
+        value: Box<Expr>,
+    },
+    If {
+        condition: Box<Expr>,
+        consequence: BlockStatement,
+        alternative: Option<BlockStatement>,
+    },
+    FunctionLiteral {
+        parameters: Vec<String>,
+        body: BlockStatement,
+    },
+    Call {
+        function: Box<Expr>,
+        arguments: Vec<Expr>,
+    },
+}
+
+pub type BlockStatement = Vec<Statement>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Let { name: String, value: Expr },
+    Return(Expr),
+    While { condition: Expr, body: BlockStatement },
+    Throw(Expr),
+    Try {
+        block: BlockStatement,
+        catch_name: String,
+        catch_block: BlockStatement,
+    },
+    Expression(Expr),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub statements: Vec<Statement>,
+}
@@ -0,0 +1,174 @@
+use std::convert::TryFrom;
+
+pub type Instructions = Vec<u8>;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Constant,
+    Pop,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    IntDiv,
+    Pow,
+    Shl,
+    Shr,
+    BitAnd,
+    BitXor,
+    BitOr,
+    True,
+    False,
+    Null,
+    Equal,
+    NotEqual,
+    GreaterThan,
+    GreaterEq,
+    LessThan,
+    LessEq,
+    Minus,
+    Not,
+    JumpNotTruthy,
+    Jump,
+    GetGlobal,
+    SetGlobal,
+    GetLocal,
+    SetLocal,
+    Assign,
+    CompoundAssign,
+    Array,
+    Hash,
+    Range,
+    Index,
+    Call,
+    ReturnValue,
+    Return,
+    GetBuiltin,
+    SetupTry,
+    PopTry,
+    Throw,
+}
+
+/// 每个 Opcode 声明顺序必须和这里一致，`Opcode as u8` 才能和 `TryFrom<u8>`
+/// 互为逆操作。
+const VARIANTS: &[Opcode] = &[
+    Opcode::Constant,
+    Opcode::Pop,
+    Opcode::Add,
+    Opcode::Sub,
+    Opcode::Mul,
+    Opcode::Div,
+    Opcode::Mod,
+    Opcode::IntDiv,
+    Opcode::Pow,
+    Opcode::Shl,
+    Opcode::Shr,
+    Opcode::BitAnd,
+    Opcode::BitXor,
+    Opcode::BitOr,
+    Opcode::True,
+    Opcode::False,
+    Opcode::Null,
+    Opcode::Equal,
+    Opcode::NotEqual,
+    Opcode::GreaterThan,
+    Opcode::GreaterEq,
+    Opcode::LessThan,
+    Opcode::LessEq,
+    Opcode::Minus,
+    Opcode::Not,
+    Opcode::JumpNotTruthy,
+    Opcode::Jump,
+    Opcode::GetGlobal,
+    Opcode::SetGlobal,
+    Opcode::GetLocal,
+    Opcode::SetLocal,
+    Opcode::Assign,
+    Opcode::CompoundAssign,
+    Opcode::Array,
+    Opcode::Hash,
+    Opcode::Range,
+    Opcode::Index,
+    Opcode::Call,
+    Opcode::ReturnValue,
+    Opcode::Return,
+    Opcode::GetBuiltin,
+    Opcode::SetupTry,
+    Opcode::PopTry,
+    Opcode::Throw,
+];
+
+pub struct Definition {
+    pub operand_widths: Vec<usize>,
+}
+
+impl Opcode {
+    pub fn definition(&self) -> Definition {
+        let widths = match self {
+            Opcode::Constant => vec![2],
+            Opcode::GetGlobal | Opcode::SetGlobal => vec![2],
+            Opcode::GetLocal | Opcode::SetLocal => vec![1],
+            Opcode::JumpNotTruthy | Opcode::Jump => vec![2],
+            Opcode::Assign => vec![2, 1],
+            Opcode::CompoundAssign => vec![2, 1, 1],
+            Opcode::Array | Opcode::Hash => vec![2],
+            Opcode::Range => vec![1],
+            Opcode::Call => vec![1],
+            Opcode::GetBuiltin => vec![1],
+            Opcode::SetupTry => vec![2],
+            _ => vec![],
+        };
+        Definition {
+            operand_widths: widths,
+        }
+    }
+}
+
+impl TryFrom<u8> for Opcode {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        VARIANTS
+            .get(value as usize)
+            .copied()
+            .ok_or_else(|| format!("unknown opcode byte {}", value))
+    }
+}
+
+pub fn make_instruction(op: Opcode, operands: &[usize]) -> Vec<u8> {
+    let def = op.definition();
+    let mut instruction = vec![op as u8];
+    for (i, width) in def.operand_widths.iter().enumerate() {
+        let operand = operands[i];
+        match width {
+            2 => instruction.extend_from_slice(&(operand as u16).to_be_bytes()),
+            1 => instruction.push(operand as u8),
+            _ => {}
+        }
+    }
+    instruction
+}
+
+/// # 按操作数宽度从指令字节流里解码操作数
+///
+/// 返回解出的操作数以及消耗的字节数，供调用方推进 `ip`。
+pub fn read_operands(def: &Definition, ins: &[u8]) -> (Vec<usize>, usize) {
+    let mut operands = vec![0; def.operand_widths.len()];
+    let mut offset = 0;
+    for (i, width) in def.operand_widths.iter().enumerate() {
+        match width {
+            2 => {
+                operands[i] = u16::from_be_bytes([ins[offset], ins[offset + 1]]) as usize;
+                offset += 2;
+            }
+            1 => {
+                operands[i] = ins[offset] as usize;
+                offset += 1;
+            }
+            _ => {}
+        }
+    }
+    (operands, offset)
+}
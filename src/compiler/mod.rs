@@ -0,0 +1,607 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::ast::{AssignOp, Expr, InfixOp, PrefixOp, Program, Statement};
+use crate::compiler::code::{make_instruction, Instructions, Opcode};
+use crate::compiler::symbol_table::{Scope, SymbolTable};
+use crate::object::{Closure, CompiledFunction, Object};
+
+pub mod code;
+pub mod symbol_table;
+
+#[derive(Debug)]
+pub struct CompileError(pub String);
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub struct Bytecode {
+    pub instructions: Instructions,
+    pub constants: Vec<Rc<Object>>,
+}
+
+/// # 字节码编译器
+///
+/// `scopes`/`symbol_tables` 以栈的形式跟踪当前正在编译的函数体，
+/// 进入函数字面量时各自 push 一层，编译完成后 pop 并打包成
+/// `CompiledFunction` 常量。
+pub struct Compiler {
+    constants: Vec<Rc<Object>>,
+    symbol_tables: Vec<Rc<RefCell<SymbolTable>>>,
+    scopes: Vec<Instructions>,
+}
+
+impl Compiler {
+    pub fn new_with_state(symbol_table: Rc<RefCell<SymbolTable>>, constants: Vec<Rc<Object>>) -> Self {
+        Compiler {
+            constants,
+            symbol_tables: vec![symbol_table],
+            scopes: vec![vec![]],
+        }
+    }
+
+    pub fn constants(&self) -> Vec<Rc<Object>> {
+        self.constants.clone()
+    }
+
+    pub fn bytecode(&self) -> Bytecode {
+        Bytecode {
+            instructions: self.scopes[0].clone(),
+            constants: self.constants.clone(),
+        }
+    }
+
+    fn symbol_table(&self) -> Rc<RefCell<SymbolTable>> {
+        self.symbol_tables.last().unwrap().clone()
+    }
+
+    fn enter_scope(&mut self) {
+        let enclosed = SymbolTable::new_enclosed(self.symbol_table());
+        self.symbol_tables.push(Rc::new(RefCell::new(enclosed)));
+        self.scopes.push(vec![]);
+    }
+
+    fn leave_scope(&mut self) -> (Instructions, usize) {
+        let table = self.symbol_tables.pop().unwrap();
+        let num_locals = table.borrow().num_definitions;
+        let instructions = self.scopes.pop().unwrap();
+        (instructions, num_locals)
+    }
+
+    fn emit(&mut self, op: Opcode, operands: &[usize]) -> usize {
+        let ins = make_instruction(op, operands);
+        let pos = self.current_instructions().len();
+        self.current_instructions_mut().extend_from_slice(&ins);
+        pos
+    }
+
+    fn current_instructions(&self) -> &Instructions {
+        self.scopes.last().unwrap()
+    }
+
+    fn current_instructions_mut(&mut self) -> &mut Instructions {
+        self.scopes.last_mut().unwrap()
+    }
+
+    fn change_operand(&mut self, op_pos: usize, operand: usize) {
+        let bytes = (operand as u16).to_be_bytes();
+        let ins = self.current_instructions_mut();
+        ins[op_pos + 1] = bytes[0];
+        ins[op_pos + 2] = bytes[1];
+    }
+
+    fn add_constant(&mut self, obj: Object) -> usize {
+        self.constants.push(Rc::new(obj));
+        self.constants.len() - 1
+    }
+
+    fn define_symbol(&mut self, name: &str) -> (usize, bool) {
+        let symbol = self.symbol_table().borrow_mut().define(name);
+        (symbol.index, symbol.scope == Scope::Local)
+    }
+
+    fn emit_set(&mut self, name: &str) {
+        let (index, is_local) = self.define_symbol(name);
+        if is_local {
+            self.emit(Opcode::SetLocal, &[index]);
+        } else {
+            self.emit(Opcode::SetGlobal, &[index]);
+        }
+    }
+
+    pub fn compile_program(&mut self, program: &Program) -> Result<(), CompileError> {
+        for stmt in &program.statements {
+            self.compile_statement(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn compile_block(&mut self, block: &[Statement]) -> Result<(), CompileError> {
+        for stmt in block {
+            self.compile_statement(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn compile_statement(&mut self, stmt: &Statement) -> Result<(), CompileError> {
+        match stmt {
+            Statement::Expression(expr) => {
+                self.compile_expr(expr)?;
+                self.emit(Opcode::Pop, &[]);
+            }
+            Statement::Let { name, value } => {
+                // 先定义符号再编译初始值，这样函数体里对自身名字的递归引用能解析成功。
+                let (index, is_local) = self.define_symbol(name);
+                self.compile_expr(value)?;
+                if is_local {
+                    self.emit(Opcode::SetLocal, &[index]);
+                } else {
+                    self.emit(Opcode::SetGlobal, &[index]);
+                }
+            }
+            Statement::Return(expr) => {
+                self.compile_expr(expr)?;
+                self.emit(Opcode::ReturnValue, &[]);
+            }
+            Statement::While { condition, body } => {
+                let cond_pos = self.current_instructions().len();
+                self.compile_expr(condition)?;
+                let jump_not_truthy_pos = self.emit(Opcode::JumpNotTruthy, &[9999]);
+                self.compile_block(body)?;
+                self.emit(Opcode::Jump, &[cond_pos]);
+                let after_pos = self.current_instructions().len();
+                self.change_operand(jump_not_truthy_pos, after_pos);
+            }
+            Statement::Throw(expr) => {
+                self.compile_expr(expr)?;
+                self.emit(Opcode::Throw, &[]);
+            }
+            Statement::Try {
+                block,
+                catch_name,
+                catch_block,
+            } => {
+                let setup_pos = self.emit(Opcode::SetupTry, &[9999]);
+                self.compile_block(block)?;
+                self.emit(Opcode::PopTry, &[]);
+                let jump_pos = self.emit(Opcode::Jump, &[9999]);
+
+                let catch_pos = self.current_instructions().len();
+                self.change_operand(setup_pos, catch_pos);
+                self.emit_set(catch_name);
+                self.compile_block(catch_block)?;
+
+                let after_pos = self.current_instructions().len();
+                self.change_operand(jump_pos, after_pos);
+            }
+        }
+        Ok(())
+    }
+
+    fn compile_expr(&mut self, expr: &Expr) -> Result<(), CompileError> {
+        match expr {
+            Expr::IntegerLiteral(n) => {
+                let idx = self.add_constant(Object::Integer(*n));
+                self.emit(Opcode::Constant, &[idx]);
+            }
+            Expr::StringLiteral(s) => {
+                let idx = self.add_constant(Object::String(s.clone()));
+                self.emit(Opcode::Constant, &[idx]);
+            }
+            Expr::BooleanLiteral(true) => {
+                self.emit(Opcode::True, &[]);
+            }
+            Expr::BooleanLiteral(false) => {
+                self.emit(Opcode::False, &[]);
+            }
+            Expr::Null => {
+                self.emit(Opcode::Null, &[]);
+            }
+            Expr::Identifier(name) => {
+                let symbol = self
+                    .symbol_table()
+                    .borrow()
+                    .resolve(name)
+                    .ok_or_else(|| CompileError(format!("undefined variable {}", name)))?;
+                if symbol.scope == Scope::Local {
+                    self.emit(Opcode::GetLocal, &[symbol.index]);
+                } else {
+                    self.emit(Opcode::GetGlobal, &[symbol.index]);
+                }
+            }
+            Expr::ArrayLiteral(elements) => {
+                for el in elements {
+                    self.compile_expr(el)?;
+                }
+                self.emit(Opcode::Array, &[elements.len()]);
+            }
+            Expr::HashLiteral(pairs) => {
+                for (k, v) in pairs {
+                    self.compile_expr(k)?;
+                    self.compile_expr(v)?;
+                }
+                self.emit(Opcode::Hash, &[pairs.len()]);
+            }
+            Expr::Prefix { op, right } => {
+                self.compile_expr(right)?;
+                match op {
+                    PrefixOp::Neg => self.emit(Opcode::Minus, &[]),
+                    PrefixOp::Not => self.emit(Opcode::Not, &[]),
+                };
+            }
+            Expr::Infix { op, left, right } => {
+                self.compile_expr(left)?;
+                self.compile_expr(right)?;
+                self.emit(Self::infix_opcode(op), &[]);
+            }
+            Expr::Index { left, index } => {
+                self.compile_expr(left)?;
+                self.compile_expr(index)?;
+                self.emit(Opcode::Index, &[]);
+            }
+            Expr::Range { start, end, inclusive } => {
+                let mut flags = 0usize;
+                if let Some(s) = start {
+                    self.compile_expr(s)?;
+                    flags |= 0b001;
+                }
+                if let Some(e) = end {
+                    self.compile_expr(e)?;
+                    flags |= 0b010;
+                }
+                if *inclusive {
+                    flags |= 0b100;
+                }
+                self.emit(Opcode::Range, &[flags]);
+            }
+            Expr::Assign { op, target, value } => {
+                self.compile_assign(op, target, value)?;
+            }
+            Expr::If {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                self.compile_expr(condition)?;
+                let jump_not_truthy_pos = self.emit(Opcode::JumpNotTruthy, &[9999]);
+                self.compile_block(consequence)?;
+                let jump_pos = self.emit(Opcode::Jump, &[9999]);
+                let after_consequence = self.current_instructions().len();
+                self.change_operand(jump_not_truthy_pos, after_consequence);
+                if let Some(alt) = alternative {
+                    self.compile_block(alt)?;
+                } else {
+                    self.emit(Opcode::Null, &[]);
+                }
+                let after_alternative = self.current_instructions().len();
+                self.change_operand(jump_pos, after_alternative);
+            }
+            Expr::FunctionLiteral { parameters, body } => {
+                self.enter_scope();
+                for param in parameters {
+                    self.symbol_table().borrow_mut().define(param);
+                }
+                self.compile_block(body)?;
+                // 兜底：函数体如果没有显式 return，落到结尾时返回 null。
+                self.emit(Opcode::Null, &[]);
+                self.emit(Opcode::ReturnValue, &[]);
+                let (instructions, num_locals) = self.leave_scope();
+                let compiled_function = CompiledFunction {
+                    instructions,
+                    num_locals,
+                    num_parameters: parameters.len(),
+                };
+                let closure = Object::Closure(Rc::new(Closure {
+                    compiled_function: Rc::new(compiled_function),
+                }));
+                let idx = self.add_constant(closure);
+                self.emit(Opcode::Constant, &[idx]);
+            }
+            Expr::Call {
+                function,
+                arguments,
+            } => {
+                self.compile_expr(function)?;
+                for arg in arguments {
+                    self.compile_expr(arg)?;
+                }
+                self.emit(Opcode::Call, &[arguments.len()]);
+            }
+        }
+        Ok(())
+    }
+
+    /// # 编译赋值/复合赋值表达式
+    ///
+    /// 目标只支持普通变量或者"变量上的下标" (`arr[i] = v`)；其它形式的左值
+    /// 在这里直接拒绝，而不是生成一条运行期才会发现问题的指令。
+    fn compile_assign(&mut self, op: &AssignOp, target: &Expr, value: &Expr) -> Result<(), CompileError> {
+        let (index, is_local, indexed) = match target {
+            Expr::Identifier(name) => {
+                // `resolve` 的返回值必须先落地再匹配：直接 match 在
+                // `self.symbol_table().borrow()` 这个临时值上会让借用一直
+                // 活到整个 match 结束，None 分支里 `define_symbol` 再去
+                // `borrow_mut()` 就会触发已借用 panic。
+                let resolved = self.symbol_table().borrow().resolve(name);
+                let symbol = match resolved {
+                    Some(symbol) => symbol,
+                    None => {
+                        let (index, is_local) = self.define_symbol(name);
+                        return self.finish_assign(op, index, is_local, false, value);
+                    }
+                };
+                (symbol.index, symbol.scope == Scope::Local, false)
+            }
+            Expr::Index { left, index: idx_expr } => {
+                let name = match left.as_ref() {
+                    Expr::Identifier(name) => name,
+                    _ => {
+                        return Err(CompileError(
+                            "assignment target must be a variable or an index into one".to_string(),
+                        ))
+                    }
+                };
+                let symbol = self
+                    .symbol_table()
+                    .borrow()
+                    .resolve(name)
+                    .ok_or_else(|| CompileError(format!("undefined variable {}", name)))?;
+                self.compile_expr(idx_expr)?;
+                (symbol.index, symbol.scope == Scope::Local, true)
+            }
+            _ => {
+                return Err(CompileError(
+                    "assignment target must be a variable or an index into one".to_string(),
+                ))
+            }
+        };
+        self.finish_assign(op, index, is_local, indexed, value)
+    }
+
+    fn finish_assign(
+        &mut self,
+        op: &AssignOp,
+        index: usize,
+        is_local: bool,
+        indexed: bool,
+        value: &Expr,
+    ) -> Result<(), CompileError> {
+        let _ = indexed;
+        self.compile_expr(value)?;
+        match op {
+            AssignOp::Assign => {
+                self.emit(Opcode::Assign, &[index, is_local as usize]);
+            }
+            _ => {
+                let bin_op = Self::assign_op_to_opcode(op);
+                self.emit(Opcode::CompoundAssign, &[index, is_local as usize, bin_op as usize]);
+            }
+        }
+        Ok(())
+    }
+
+    fn assign_op_to_opcode(op: &AssignOp) -> Opcode {
+        match op {
+            AssignOp::Assign => unreachable!("plain assignment has no binary opcode"),
+            AssignOp::AddAssign => Opcode::Add,
+            AssignOp::SubAssign => Opcode::Sub,
+            AssignOp::MulAssign => Opcode::Mul,
+            AssignOp::DivAssign => Opcode::Div,
+            AssignOp::ModAssign => Opcode::Mod,
+        }
+    }
+
+    fn infix_opcode(op: &InfixOp) -> Opcode {
+        match op {
+            InfixOp::Add => Opcode::Add,
+            InfixOp::Sub => Opcode::Sub,
+            InfixOp::Mul => Opcode::Mul,
+            InfixOp::Div => Opcode::Div,
+            InfixOp::Mod => Opcode::Mod,
+            InfixOp::IntDiv => Opcode::IntDiv,
+            InfixOp::Pow => Opcode::Pow,
+            InfixOp::Shl => Opcode::Shl,
+            InfixOp::Shr => Opcode::Shr,
+            InfixOp::BitAnd => Opcode::BitAnd,
+            InfixOp::BitXor => Opcode::BitXor,
+            InfixOp::BitOr => Opcode::BitOr,
+            InfixOp::Lt => Opcode::LessThan,
+            InfixOp::LtEq => Opcode::LessEq,
+            InfixOp::Gt => Opcode::GreaterThan,
+            InfixOp::GtEq => Opcode::GreaterEq,
+            InfixOp::Eq => Opcode::Equal,
+            InfixOp::NotEq => Opcode::NotEqual,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::RuntimeError;
+    use crate::parser::Parser;
+    use crate::vm::Vm;
+
+    fn run(input: &str) -> Rc<Object> {
+        let mut parser = Parser::from(input.to_string());
+        let program = parser.parse_program();
+        assert!(parser.errors().is_empty(), "parser errors: {:?}", parser.errors());
+
+        let symbol_table = Rc::new(RefCell::new(SymbolTable::new()));
+        let mut compiler = Compiler::new_with_state(symbol_table, vec![]);
+        compiler.compile_program(&program).expect("compile error");
+
+        let mut vm = Vm::new(compiler.bytecode());
+        vm.run().expect("vm error");
+        vm.last_popped_stack_element().unwrap()
+    }
+
+    fn run_checked(input: &str) -> Result<Rc<Object>, RuntimeError> {
+        let mut parser = Parser::from(input.to_string());
+        let program = parser.parse_program();
+        assert!(parser.errors().is_empty(), "parser errors: {:?}", parser.errors());
+
+        let symbol_table = Rc::new(RefCell::new(SymbolTable::new()));
+        let mut compiler = Compiler::new_with_state(symbol_table, vec![]);
+        compiler.compile_program(&program).expect("compile error");
+
+        let mut vm = Vm::new(compiler.bytecode());
+        vm.run()?;
+        Ok(vm.last_popped_stack_element().unwrap())
+    }
+
+    #[test]
+    fn try_catch_recovers_thrown_value() {
+        let result = run(r#"try { throw "boom"; } catch (e) { e }"#);
+        assert_eq!(*result, Object::Error(Rc::new(Object::String("boom".to_string()))));
+    }
+
+    #[test]
+    fn try_catch_recovers_runtime_error() {
+        let result = run("let x = 0; try { 1 / x; } catch (e) { 42 }");
+        assert_eq!(*result, Object::Integer(42));
+    }
+
+    #[test]
+    fn uncaught_error_propagates() {
+        let mut parser = Parser::from("1 / 0;".to_string());
+        let program = parser.parse_program();
+        let symbol_table = Rc::new(RefCell::new(SymbolTable::new()));
+        let mut compiler = Compiler::new_with_state(symbol_table, vec![]);
+        compiler.compile_program(&program).unwrap();
+        let mut vm = Vm::new(compiler.bytecode());
+        assert!(vm.run().is_err());
+    }
+
+    #[test]
+    fn compound_assign_on_a_plain_variable() {
+        let result = run("let x = 1; x += 2; x");
+        assert_eq!(*result, Object::Integer(3));
+    }
+
+    #[test]
+    fn compound_assign_on_an_array_element() {
+        let result = run("let a = [1, 2, 3]; a[1] *= 10; a[1]");
+        assert_eq!(*result, Object::Integer(20));
+    }
+
+    #[test]
+    fn compound_assign_with_an_out_of_range_index_is_an_error_instead_of_a_panic() {
+        assert!(run_checked("let a = [1, 2, 3]; a[10] += 1;").is_err());
+        assert!(run_checked("let a = [1, 2, 3]; a[-1] += 1;").is_err());
+    }
+
+    #[test]
+    fn compound_assign_on_a_hash_value() {
+        let result = run(r#"let h = {"n": 1}; h["n"] -= 1; h["n"]"#);
+        assert_eq!(*result, Object::Integer(0));
+    }
+
+    #[test]
+    fn plain_assign_declares_then_updates_a_global() {
+        let result = run("x = 1; x = x + 1; x");
+        assert_eq!(*result, Object::Integer(2));
+    }
+
+    #[test]
+    fn array_slice_with_both_endpoints() {
+        let result = run("[1, 2, 3, 4, 5][1..3]");
+        assert_eq!(
+            *result,
+            Object::Array(RefCell::new(vec![Object::Integer(2), Object::Integer(3)]))
+        );
+    }
+
+    #[test]
+    fn array_slice_is_inclusive_with_dotdoteq() {
+        let result = run("[1, 2, 3, 4, 5][1..=3]");
+        assert_eq!(
+            *result,
+            Object::Array(RefCell::new(vec![
+                Object::Integer(2),
+                Object::Integer(3),
+                Object::Integer(4)
+            ]))
+        );
+    }
+
+    #[test]
+    fn array_slice_with_omitted_endpoints() {
+        assert_eq!(
+            *run("[1, 2, 3][..2]"),
+            Object::Array(RefCell::new(vec![Object::Integer(1), Object::Integer(2)]))
+        );
+        assert_eq!(
+            *run("[1, 2, 3][1..]"),
+            Object::Array(RefCell::new(vec![Object::Integer(2), Object::Integer(3)]))
+        );
+    }
+
+    #[test]
+    fn string_slice() {
+        let result = run(r#""hello"[1..4]"#);
+        assert_eq!(*result, Object::String("ell".to_string()));
+    }
+
+    #[test]
+    fn modulo_and_integer_division() {
+        assert_eq!(*run("7 % 2"), Object::Integer(1));
+        assert_eq!(*run("7 // 2"), Object::Integer(3));
+        assert_eq!(*run("-7 // 2"), Object::Integer(-4));
+    }
+
+    #[test]
+    fn division_by_zero_is_an_error_for_mod_and_intdiv() {
+        assert!(run_checked("7 % 0").is_err());
+        assert!(run_checked("7 // 0").is_err());
+    }
+
+    #[test]
+    fn exponent_and_bitwise_operators() {
+        assert_eq!(*run("2 ** 10"), Object::Integer(1024));
+        assert_eq!(*run("1 << 4"), Object::Integer(16));
+        assert_eq!(*run("16 >> 4"), Object::Integer(1));
+        assert_eq!(*run("6 & 3"), Object::Integer(2));
+        assert_eq!(*run("6 ^ 3"), Object::Integer(5));
+        assert_eq!(*run("6 | 1"), Object::Integer(7));
+    }
+
+    #[test]
+    fn negative_exponent_is_rejected() {
+        assert!(run_checked("2 ** -1").is_err());
+    }
+
+    #[test]
+    fn large_exponent_errors_instead_of_silently_truncating() {
+        assert!(run_checked("2 ** 4294967296").is_err());
+    }
+
+    #[test]
+    fn exponent_that_overflows_i64_is_an_error_instead_of_a_panic() {
+        assert!(run_checked("2 ** 63").is_err());
+    }
+
+    #[test]
+    fn shift_amount_out_of_range_is_an_error() {
+        assert!(run_checked("1 << 64").is_err());
+        assert!(run_checked("1 >> -1").is_err());
+    }
+
+    #[test]
+    fn string_and_array_ordering() {
+        assert_eq!(*run(r#""abc" < "abd""#), Object::Boolean(true));
+        assert_eq!(*run(r#""abc" >= "abc""#), Object::Boolean(true));
+        assert_eq!(*run("[1, 2, 3] < [1, 2, 4]"), Object::Boolean(true));
+        assert_eq!(*run("[1, 2] < [1, 2, 3]"), Object::Boolean(true));
+        assert_eq!(*run("[1, 2, 3] <= [1, 2, 3]"), Object::Boolean(true));
+    }
+
+    #[test]
+    fn incomparable_types_report_an_error_instead_of_panicking() {
+        assert!(run_checked(r#"1 < "1""#).is_err());
+        assert!(run_checked("[1] > 1").is_err());
+    }
+}
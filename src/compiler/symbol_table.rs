@@ -0,0 +1,58 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Global,
+    Local,
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub scope: Scope,
+    pub index: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    store: HashMap<String, Symbol>,
+    outer: Option<Rc<RefCell<SymbolTable>>>,
+    pub num_definitions: usize,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn new_enclosed(outer: Rc<RefCell<SymbolTable>>) -> Self {
+        SymbolTable {
+            store: HashMap::new(),
+            outer: Some(outer),
+            num_definitions: 0,
+        }
+    }
+
+    pub fn define(&mut self, name: &str) -> Symbol {
+        let scope = if self.outer.is_some() {
+            Scope::Local
+        } else {
+            Scope::Global
+        };
+        let symbol = Symbol {
+            scope,
+            index: self.num_definitions,
+        };
+        self.store.insert(name.to_string(), symbol.clone());
+        self.num_definitions += 1;
+        symbol
+    }
+
+    pub fn resolve(&self, name: &str) -> Option<Symbol> {
+        match self.store.get(name) {
+            Some(symbol) => Some(symbol.clone()),
+            None => self.outer.as_ref().and_then(|outer| outer.borrow().resolve(name)),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::token::{lookup_ident, Token};
+
+pub struct Lexer {
+    input: Vec<char>,
+    position: usize,
+    read_position: usize,
+    ch: char,
+}
+
+impl Lexer {
+    pub fn new(input: String) -> Self {
+        let mut lexer = Lexer {
+            input: input.chars().collect(),
+            position: 0,
+            read_position: 0,
+            ch: '\0',
+        };
+        lexer.read_char();
+        lexer
+    }
+
+    fn read_char(&mut self) {
+        self.ch = self.input.get(self.read_position).copied().unwrap_or('\0');
+        self.position = self.read_position;
+        self.read_position += 1;
+    }
+
+    fn peek_char(&self) -> char {
+        self.input.get(self.read_position).copied().unwrap_or('\0')
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.ch.is_whitespace() {
+            self.read_char();
+        }
+    }
+
+    fn read_while<F: Fn(char) -> bool>(&mut self, pred: F) -> String {
+        let start = self.position;
+        while pred(self.ch) {
+            self.read_char();
+        }
+        self.input[start..self.position].iter().collect()
+    }
+
+    fn read_string(&mut self) -> String {
+        let start = self.position + 1;
+        loop {
+            self.read_char();
+            if self.ch == '"' || self.ch == '\0' {
+                break;
+            }
+        }
+        self.input[start..self.position].iter().collect()
+    }
+
+    pub fn next_token(&mut self) -> Token {
+        self.skip_whitespace();
+        let tok = match self.ch {
+            '=' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::Eq
+                } else {
+                    Token::Assign
+                }
+            }
+            '+' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::PlusAssign
+                } else {
+                    Token::Plus
+                }
+            }
+            '-' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::MinusAssign
+                } else {
+                    Token::Minus
+                }
+            }
+            '!' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::NotEq
+                } else {
+                    Token::Bang
+                }
+            }
+            '*' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::AsteriskAssign
+                } else if self.peek_char() == '*' {
+                    self.read_char();
+                    Token::StarStar
+                } else {
+                    Token::Asterisk
+                }
+            }
+            '/' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::SlashAssign
+                } else if self.peek_char() == '/' {
+                    self.read_char();
+                    Token::SlashSlash
+                } else {
+                    Token::Slash
+                }
+            }
+            '%' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::PercentAssign
+                } else {
+                    Token::Percent
+                }
+            }
+            '&' => Token::Amp,
+            '|' => Token::Pipe,
+            '^' => Token::Caret,
+            '<' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::LtEq
+                } else if self.peek_char() == '<' {
+                    self.read_char();
+                    Token::Shl
+                } else {
+                    Token::Lt
+                }
+            }
+            '>' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::GtEq
+                } else if self.peek_char() == '>' {
+                    self.read_char();
+                    Token::Shr
+                } else {
+                    Token::Gt
+                }
+            }
+            '.' => {
+                if self.peek_char() == '.' {
+                    self.read_char();
+                    if self.peek_char() == '=' {
+                        self.read_char();
+                        Token::DotDotEq
+                    } else {
+                        Token::DotDot
+                    }
+                } else {
+                    Token::Illegal(".".to_string())
+                }
+            }
+            ',' => Token::Comma,
+            ';' => Token::Semicolon,
+            ':' => Token::Colon,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '"' => {
+                let s = self.read_string();
+                Token::Str(s)
+            }
+            '\0' => Token::Eof,
+            c if c.is_alphabetic() || c == '_' => {
+                let ident = self.read_while(|c| c.is_alphanumeric() || c == '_');
+                return lookup_ident(&ident);
+            }
+            c if c.is_ascii_digit() => {
+                let digits = self.read_while(|c| c.is_ascii_digit());
+                return match digits.parse::<i64>() {
+                    Ok(n) => Token::Int(n),
+                    Err(_) => Token::Illegal(digits),
+                };
+            }
+            c => Token::Illegal(c.to_string()),
+        };
+        self.read_char();
+        tok
+    }
+}
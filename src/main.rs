@@ -0,0 +1,14 @@
+#![allow(clippy::result_large_err)]
+
+mod ast;
+mod compiler;
+mod lexer;
+mod object;
+mod parser;
+mod repl;
+mod token;
+mod vm;
+
+fn main() {
+    repl::start();
+}
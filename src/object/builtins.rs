@@ -0,0 +1,16 @@
+use crate::object::Object;
+
+#[allow(dead_code)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub builtin: Object,
+}
+
+/// 当前没有内置函数实现；占位数组保留扩展位置，`lookup`/`get_builtin` 已经
+/// 按下标工作，后续新增内置函数只需往这里追加。
+pub const BUILTINS: [Builtin; 0] = [];
+
+#[allow(dead_code)]
+pub fn lookup(name: &str) -> Option<usize> {
+    BUILTINS.iter().position(|b| b.name == name)
+}
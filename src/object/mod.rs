@@ -0,0 +1,183 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::compiler::code::{Instructions, Opcode};
+
+pub mod builtins;
+
+pub type BuiltinFn = fn(Vec<Object>) -> Result<Object, RuntimeError>;
+
+#[derive(Debug, Clone)]
+pub struct CompiledFunction {
+    pub instructions: Instructions,
+    pub num_locals: usize,
+    pub num_parameters: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Closure {
+    pub compiled_function: Rc<CompiledFunction>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Object {
+    Integer(i64),
+    String(String),
+    Boolean(bool),
+    Null,
+    Array(RefCell<Vec<Object>>),
+    Hash(RefCell<HashMap<HashKey, Object>>),
+    Closure(Rc<Closure>),
+    /// 内置函数占位：`object::builtins::BUILTINS` 目前是空数组，尚无任何
+    /// 内置函数真正构造出这个变体。
+    #[allow(dead_code)]
+    Builtin(BuiltinFn),
+    /// 首个公民异常值：`throw`/内部运行时错误都会被包装成它再沿 try/catch 链展开。
+    Error(Rc<Object>),
+    /// 切片/区间字面量 `a..b`、`a..=b`；端点省略时退化为开区间。
+    Range {
+        start: Option<i64>,
+        end: Option<i64>,
+        inclusive: bool,
+    },
+}
+
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Object::Integer(a), Object::Integer(b)) => a == b,
+            (Object::String(a), Object::String(b)) => a == b,
+            (Object::Boolean(a), Object::Boolean(b)) => a == b,
+            (Object::Null, Object::Null) => true,
+            (Object::Array(a), Object::Array(b)) => a == b,
+            (Object::Closure(a), Object::Closure(b)) => Rc::ptr_eq(a, b),
+            (Object::Builtin(a), Object::Builtin(b)) => std::ptr::eq(a, b),
+            (Object::Error(a), Object::Error(b)) => a == b,
+            (
+                Object::Range {
+                    start: s1,
+                    end: e1,
+                    inclusive: i1,
+                },
+                Object::Range {
+                    start: s2,
+                    end: e2,
+                    inclusive: i2,
+                },
+            ) => s1 == s2 && e1 == e2 && i1 == i2,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Object::Integer(n) => write!(f, "{}", n),
+            Object::String(s) => write!(f, "{}", s),
+            Object::Boolean(b) => write!(f, "{}", b),
+            Object::Null => write!(f, "null"),
+            Object::Array(items) => {
+                let items = items.borrow();
+                let parts: Vec<String> = items.iter().map(|i| i.to_string()).collect();
+                write!(f, "[{}]", parts.join(", "))
+            }
+            Object::Hash(_) => write!(f, "{{hash}}"),
+            Object::Closure(_) => write!(f, "<closure>"),
+            Object::Builtin(_) => write!(f, "<builtin>"),
+            Object::Error(inner) => write!(f, "error: {}", inner),
+            Object::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let op = if *inclusive { "..=" } else { ".." };
+                write!(
+                    f,
+                    "{}{}{}",
+                    start.map(|v| v.to_string()).unwrap_or_default(),
+                    op,
+                    end.map(|v| v.to_string()).unwrap_or_default()
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HashKey {
+    Integer(i64),
+    String(String),
+    Boolean(bool),
+}
+
+impl HashKey {
+    pub fn from_object(obj: &Object) -> Result<HashKey, RuntimeError> {
+        match obj {
+            Object::Integer(n) => Ok(HashKey::Integer(*n)),
+            Object::String(s) => Ok(HashKey::String(s.clone())),
+            Object::Boolean(b) => Ok(HashKey::Boolean(*b)),
+            _ => Err(RuntimeError::UnHashableType(obj.clone())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeError {
+    UnSupportedUnOperation(Opcode, Object),
+    UnSupportedBinOperation(Opcode, Object, Object),
+    UnSupportedBinOperator(Opcode),
+    UnSupportedIndexOperation(Object, Object),
+    UnHashableType(Object),
+    ByZero(Object, Object),
+    WrongArgumentCount(usize, usize),
+    CustomErrMsg(String),
+    /// 目前没有任何调用点会构造这个变体，保留它是因为 `let` 重声明检查是
+    /// 一个计划中但尚未接入编译器的功能。
+    #[allow(dead_code)]
+    VariableHasBeenDeclared(String),
+    /// `throw` 抛出的值找不到任何 try/catch 处理帧时，不可恢复地向外传播；
+    /// 内部运行时错误找不到处理帧时按原本的错误变体传播，不会变成它。
+    Uncaught(Object),
+    /// REPL 的 Ctrl-C 处理器置位了中断标志，运行中的循环/递归被协作式中止。
+    Interrupted,
+    /// 调用帧数超过 `frame_max`，用来代替真的爆栈崩溃。
+    StackOverflow(usize),
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeError::UnSupportedUnOperation(op, obj) => {
+                write!(f, "unsupported unary operation: {:?} {}", op, obj)
+            }
+            RuntimeError::UnSupportedBinOperation(op, left, right) => {
+                write!(f, "unsupported binary operation: {} {:?} {}", left, op, right)
+            }
+            RuntimeError::UnSupportedBinOperator(op) => {
+                write!(f, "unsupported binary operator: {:?}", op)
+            }
+            RuntimeError::UnSupportedIndexOperation(obj, index) => {
+                write!(f, "index operator not supported: {}[{}]", obj, index)
+            }
+            RuntimeError::UnHashableType(obj) => write!(f, "unusable as hash key: {}", obj),
+            RuntimeError::ByZero(left, right) => {
+                write!(f, "division by zero: {} / {}", left, right)
+            }
+            RuntimeError::WrongArgumentCount(want, got) => {
+                write!(f, "wrong number of arguments: want={}, got={}", want, got)
+            }
+            RuntimeError::CustomErrMsg(msg) => write!(f, "{}", msg),
+            RuntimeError::VariableHasBeenDeclared(name) => {
+                write!(f, "variable has already been declared: {}", name)
+            }
+            RuntimeError::Uncaught(obj) => write!(f, "uncaught exception: {}", obj),
+            RuntimeError::Interrupted => write!(f, "interrupted"),
+            RuntimeError::StackOverflow(max) => {
+                write!(f, "stack overflow: exceeded maximum call depth of {}", max)
+            }
+        }
+    }
+}
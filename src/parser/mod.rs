@@ -0,0 +1,527 @@
+use crate::ast::{AssignOp, BlockStatement, Expr, InfixOp, PrefixOp, Program, Statement};
+use crate::lexer::Lexer;
+use crate::token::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Precedence {
+    Lowest,
+    Assign,
+    Range,
+    Equals,
+    LessGreater,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
+    Sum,
+    Product,
+    Power,
+    Prefix,
+    Call,
+    Index,
+}
+
+pub struct Parser {
+    lexer: Lexer,
+    cur_token: Token,
+    peek_token: Token,
+    errors: Vec<String>,
+}
+
+impl Parser {
+    pub fn from(input: String) -> Self {
+        let mut lexer = Lexer::new(input);
+        let cur_token = lexer.next_token();
+        let peek_token = lexer.next_token();
+        Parser {
+            lexer,
+            cur_token,
+            peek_token,
+            errors: vec![],
+        }
+    }
+
+    pub fn errors(&self) -> &Vec<String> {
+        &self.errors
+    }
+
+    fn next_token(&mut self) {
+        self.cur_token = self.peek_token.clone();
+        self.peek_token = self.lexer.next_token();
+    }
+
+    fn expect_peek(&mut self, tok: &Token) -> bool {
+        if std::mem::discriminant(&self.peek_token) == std::mem::discriminant(tok) {
+            self.next_token();
+            true
+        } else {
+            self.errors.push(format!(
+                "expected next token to be {:?}, got {:?} instead",
+                tok, self.peek_token
+            ));
+            false
+        }
+    }
+
+    pub fn parse_program(&mut self) -> Program {
+        let mut program = Program::default();
+        while self.cur_token != Token::Eof {
+            if let Some(stmt) = self.parse_statement() {
+                program.statements.push(stmt);
+            }
+            self.next_token();
+        }
+        program
+    }
+
+    fn parse_statement(&mut self) -> Option<Statement> {
+        match &self.cur_token {
+            Token::Let => self.parse_let_statement(),
+            Token::Return => self.parse_return_statement(),
+            Token::While => self.parse_while_statement(),
+            Token::Throw => self.parse_throw_statement(),
+            Token::Try => self.parse_try_statement(),
+            _ => self.parse_expression_statement(),
+        }
+    }
+
+    fn parse_let_statement(&mut self) -> Option<Statement> {
+        let name = match &self.peek_token {
+            Token::Ident(name) => name.clone(),
+            _ => {
+                self.errors.push("expected identifier after let".to_string());
+                return None;
+            }
+        };
+        self.next_token();
+        if !self.expect_peek(&Token::Assign) {
+            return None;
+        }
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+        if self.peek_token == Token::Semicolon {
+            self.next_token();
+        }
+        Some(Statement::Let { name, value })
+    }
+
+    fn parse_return_statement(&mut self) -> Option<Statement> {
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+        if self.peek_token == Token::Semicolon {
+            self.next_token();
+        }
+        Some(Statement::Return(value))
+    }
+
+    fn parse_while_statement(&mut self) -> Option<Statement> {
+        if !self.expect_peek(&Token::LParen) {
+            return None;
+        }
+        self.next_token();
+        let condition = self.parse_expression(Precedence::Lowest)?;
+        if !self.expect_peek(&Token::RParen) {
+            return None;
+        }
+        if !self.expect_peek(&Token::LBrace) {
+            return None;
+        }
+        let body = self.parse_block_statement();
+        Some(Statement::While { condition, body })
+    }
+
+    fn parse_throw_statement(&mut self) -> Option<Statement> {
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+        if self.peek_token == Token::Semicolon {
+            self.next_token();
+        }
+        Some(Statement::Throw(value))
+    }
+
+    fn parse_try_statement(&mut self) -> Option<Statement> {
+        if !self.expect_peek(&Token::LBrace) {
+            return None;
+        }
+        let block = self.parse_block_statement();
+        if !self.expect_peek(&Token::Catch) {
+            return None;
+        }
+        if !self.expect_peek(&Token::LParen) {
+            return None;
+        }
+        let catch_name = match &self.peek_token {
+            Token::Ident(name) => name.clone(),
+            _ => {
+                self.errors.push("expected identifier in catch".to_string());
+                return None;
+            }
+        };
+        self.next_token();
+        if !self.expect_peek(&Token::RParen) {
+            return None;
+        }
+        if !self.expect_peek(&Token::LBrace) {
+            return None;
+        }
+        let catch_block = self.parse_block_statement();
+        Some(Statement::Try {
+            block,
+            catch_name,
+            catch_block,
+        })
+    }
+
+    fn parse_expression_statement(&mut self) -> Option<Statement> {
+        let expr = self.parse_expression(Precedence::Lowest)?;
+        if self.peek_token == Token::Semicolon {
+            self.next_token();
+        }
+        Some(Statement::Expression(expr))
+    }
+
+    fn parse_block_statement(&mut self) -> BlockStatement {
+        let mut statements = vec![];
+        self.next_token();
+        while self.cur_token != Token::RBrace && self.cur_token != Token::Eof {
+            if let Some(stmt) = self.parse_statement() {
+                statements.push(stmt);
+            }
+            self.next_token();
+        }
+        statements
+    }
+
+    fn peek_precedence(&self) -> Precedence {
+        Self::precedence_of(&self.peek_token)
+    }
+
+    fn precedence_of(tok: &Token) -> Precedence {
+        match tok {
+            Token::Assign
+            | Token::PlusAssign
+            | Token::MinusAssign
+            | Token::AsteriskAssign
+            | Token::SlashAssign
+            | Token::PercentAssign => Precedence::Assign,
+            Token::DotDot | Token::DotDotEq => Precedence::Range,
+            Token::Eq | Token::NotEq => Precedence::Equals,
+            Token::Lt | Token::LtEq | Token::Gt | Token::GtEq => Precedence::LessGreater,
+            Token::Pipe => Precedence::BitOr,
+            Token::Caret => Precedence::BitXor,
+            Token::Amp => Precedence::BitAnd,
+            Token::Shl | Token::Shr => Precedence::Shift,
+            Token::Plus | Token::Minus => Precedence::Sum,
+            Token::Slash | Token::Asterisk | Token::Percent | Token::SlashSlash => Precedence::Product,
+            Token::StarStar => Precedence::Power,
+            Token::LParen => Precedence::Call,
+            Token::LBracket => Precedence::Index,
+            _ => Precedence::Lowest,
+        }
+    }
+
+    fn parse_expression(&mut self, precedence: Precedence) -> Option<Expr> {
+        let mut left = self.parse_prefix()?;
+
+        while self.peek_token != Token::Semicolon && precedence < self.peek_precedence() {
+            match &self.peek_token {
+                Token::Plus
+                | Token::Minus
+                | Token::Slash
+                | Token::Asterisk
+                | Token::Percent
+                | Token::SlashSlash
+                | Token::StarStar
+                | Token::Shl
+                | Token::Shr
+                | Token::Amp
+                | Token::Pipe
+                | Token::Caret
+                | Token::Lt
+                | Token::LtEq
+                | Token::Gt
+                | Token::GtEq
+                | Token::Eq
+                | Token::NotEq => {
+                    self.next_token();
+                    left = self.parse_infix_expression(left)?;
+                }
+                Token::Assign
+                | Token::PlusAssign
+                | Token::MinusAssign
+                | Token::AsteriskAssign
+                | Token::SlashAssign
+                | Token::PercentAssign => {
+                    self.next_token();
+                    left = self.parse_assign_expression(left)?;
+                }
+                Token::DotDot | Token::DotDotEq => {
+                    self.next_token();
+                    left = self.parse_range_expression(Some(left))?;
+                }
+                Token::LParen => {
+                    self.next_token();
+                    left = self.parse_call_expression(left)?;
+                }
+                Token::LBracket => {
+                    self.next_token();
+                    left = self.parse_index_expression(left)?;
+                }
+                _ => break,
+            }
+        }
+        Some(left)
+    }
+
+    fn parse_prefix(&mut self) -> Option<Expr> {
+        match self.cur_token.clone() {
+            Token::Ident(name) => Some(Expr::Identifier(name)),
+            Token::Int(n) => Some(Expr::IntegerLiteral(n)),
+            Token::Str(s) => Some(Expr::StringLiteral(s)),
+            Token::True => Some(Expr::BooleanLiteral(true)),
+            Token::False => Some(Expr::BooleanLiteral(false)),
+            Token::Null => Some(Expr::Null),
+            Token::Bang => {
+                self.next_token();
+                let right = self.parse_expression(Precedence::Prefix)?;
+                Some(Expr::Prefix {
+                    op: PrefixOp::Not,
+                    right: Box::new(right),
+                })
+            }
+            Token::Minus => {
+                self.next_token();
+                let right = self.parse_expression(Precedence::Prefix)?;
+                Some(Expr::Prefix {
+                    op: PrefixOp::Neg,
+                    right: Box::new(right),
+                })
+            }
+            Token::DotDot | Token::DotDotEq => self.parse_range_expression(None),
+            Token::LParen => {
+                self.next_token();
+                let expr = self.parse_expression(Precedence::Lowest)?;
+                if !self.expect_peek(&Token::RParen) {
+                    return None;
+                }
+                Some(expr)
+            }
+            Token::LBracket => self.parse_array_literal(),
+            Token::LBrace => self.parse_hash_literal(),
+            Token::If => self.parse_if_expression(),
+            Token::Function => self.parse_function_literal(),
+            tok => {
+                self.errors
+                    .push(format!("no prefix parse function for {:?}", tok));
+                None
+            }
+        }
+    }
+
+    fn parse_infix_expression(&mut self, left: Expr) -> Option<Expr> {
+        let op = match self.cur_token {
+            Token::Plus => InfixOp::Add,
+            Token::Minus => InfixOp::Sub,
+            Token::Asterisk => InfixOp::Mul,
+            Token::Slash => InfixOp::Div,
+            Token::Percent => InfixOp::Mod,
+            Token::SlashSlash => InfixOp::IntDiv,
+            Token::StarStar => InfixOp::Pow,
+            Token::Shl => InfixOp::Shl,
+            Token::Shr => InfixOp::Shr,
+            Token::Amp => InfixOp::BitAnd,
+            Token::Caret => InfixOp::BitXor,
+            Token::Pipe => InfixOp::BitOr,
+            Token::Lt => InfixOp::Lt,
+            Token::LtEq => InfixOp::LtEq,
+            Token::Gt => InfixOp::Gt,
+            Token::GtEq => InfixOp::GtEq,
+            Token::Eq => InfixOp::Eq,
+            Token::NotEq => InfixOp::NotEq,
+            _ => unreachable!(),
+        };
+        let precedence = Self::precedence_of(&self.cur_token);
+        self.next_token();
+        let right = self.parse_expression(precedence)?;
+        Some(Expr::Infix {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+
+    fn parse_assign_expression(&mut self, target: Expr) -> Option<Expr> {
+        let op = match self.cur_token {
+            Token::Assign => AssignOp::Assign,
+            Token::PlusAssign => AssignOp::AddAssign,
+            Token::MinusAssign => AssignOp::SubAssign,
+            Token::AsteriskAssign => AssignOp::MulAssign,
+            Token::SlashAssign => AssignOp::DivAssign,
+            Token::PercentAssign => AssignOp::ModAssign,
+            _ => unreachable!(),
+        };
+        if !matches!(target, Expr::Identifier(_) | Expr::Index { .. }) {
+            self.errors
+                .push("left-hand side of assignment must be a variable or index expression".to_string());
+            return None;
+        }
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+        Some(Expr::Assign {
+            op,
+            target: Box::new(target),
+            value: Box::new(value),
+        })
+    }
+
+    /// # 解析区间表达式 `a..b` / `a..=b`，以及省略端点的 `..b` / `a..` / `..`
+    fn parse_range_expression(&mut self, start: Option<Expr>) -> Option<Expr> {
+        let inclusive = self.cur_token == Token::DotDotEq;
+        let end = if Self::is_range_terminator(&self.peek_token) {
+            None
+        } else {
+            self.next_token();
+            Some(Box::new(self.parse_expression(Precedence::Range)?))
+        };
+        Some(Expr::Range {
+            start: start.map(Box::new),
+            end,
+            inclusive,
+        })
+    }
+
+    fn is_range_terminator(tok: &Token) -> bool {
+        matches!(
+            tok,
+            Token::RBracket | Token::RParen | Token::RBrace | Token::Comma | Token::Semicolon | Token::Eof
+        )
+    }
+
+    fn parse_call_expression(&mut self, function: Expr) -> Option<Expr> {
+        let arguments = self.parse_expression_list(Token::RParen)?;
+        Some(Expr::Call {
+            function: Box::new(function),
+            arguments,
+        })
+    }
+
+    fn parse_index_expression(&mut self, left: Expr) -> Option<Expr> {
+        self.next_token();
+        let index = self.parse_expression(Precedence::Lowest)?;
+        if !self.expect_peek(&Token::RBracket) {
+            return None;
+        }
+        Some(Expr::Index {
+            left: Box::new(left),
+            index: Box::new(index),
+        })
+    }
+
+    fn parse_expression_list(&mut self, end: Token) -> Option<Vec<Expr>> {
+        let mut list = vec![];
+        if self.peek_token == end {
+            self.next_token();
+            return Some(list);
+        }
+        self.next_token();
+        list.push(self.parse_expression(Precedence::Lowest)?);
+        while self.peek_token == Token::Comma {
+            self.next_token();
+            self.next_token();
+            list.push(self.parse_expression(Precedence::Lowest)?);
+        }
+        if !self.expect_peek(&end) {
+            return None;
+        }
+        Some(list)
+    }
+
+    fn parse_array_literal(&mut self) -> Option<Expr> {
+        let elements = self.parse_expression_list(Token::RBracket)?;
+        Some(Expr::ArrayLiteral(elements))
+    }
+
+    fn parse_hash_literal(&mut self) -> Option<Expr> {
+        let mut pairs = vec![];
+        while self.peek_token != Token::RBrace {
+            self.next_token();
+            let key = self.parse_expression(Precedence::Lowest)?;
+            if !self.expect_peek(&Token::Colon) {
+                return None;
+            }
+            self.next_token();
+            let value = self.parse_expression(Precedence::Lowest)?;
+            pairs.push((key, value));
+            if self.peek_token != Token::RBrace && !self.expect_peek(&Token::Comma) {
+                return None;
+            }
+        }
+        if !self.expect_peek(&Token::RBrace) {
+            return None;
+        }
+        Some(Expr::HashLiteral(pairs))
+    }
+
+    fn parse_if_expression(&mut self) -> Option<Expr> {
+        if !self.expect_peek(&Token::LParen) {
+            return None;
+        }
+        self.next_token();
+        let condition = self.parse_expression(Precedence::Lowest)?;
+        if !self.expect_peek(&Token::RParen) {
+            return None;
+        }
+        if !self.expect_peek(&Token::LBrace) {
+            return None;
+        }
+        let consequence = self.parse_block_statement();
+        let alternative = if self.peek_token == Token::Else {
+            self.next_token();
+            if !self.expect_peek(&Token::LBrace) {
+                return None;
+            }
+            Some(self.parse_block_statement())
+        } else {
+            None
+        };
+        Some(Expr::If {
+            condition: Box::new(condition),
+            consequence,
+            alternative,
+        })
+    }
+
+    fn parse_function_literal(&mut self) -> Option<Expr> {
+        if !self.expect_peek(&Token::LParen) {
+            return None;
+        }
+        let mut parameters = vec![];
+        if self.peek_token != Token::RParen {
+            self.next_token();
+            loop {
+                match &self.cur_token {
+                    Token::Ident(name) => parameters.push(name.clone()),
+                    _ => {
+                        self.errors
+                            .push("expected identifier in parameter list".to_string());
+                        return None;
+                    }
+                }
+                if self.peek_token == Token::Comma {
+                    self.next_token();
+                    self.next_token();
+                } else {
+                    break;
+                }
+            }
+        }
+        if !self.expect_peek(&Token::RParen) {
+            return None;
+        }
+        if !self.expect_peek(&Token::LBrace) {
+            return None;
+        }
+        let body = self.parse_block_statement();
+        Some(Expr::FunctionLiteral { parameters, body })
+    }
+}
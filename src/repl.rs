@@ -1,28 +1,123 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rustyline::DefaultEditor;
+use rustyline::error::ReadlineError;
+
+use crate::compiler::Compiler;
+use crate::compiler::symbol_table::SymbolTable;
+use crate::object::Object;
 use crate::parser::Parser;
-use std::io;
-use std::io::Write;
+use crate::vm::Vm;
 
 const PROMPT: &str = ">> ";
+const CONTINUE_PROMPT: &str = "... ";
+const HISTORY_FILE: &str = ".myalian_history";
 
+/// # 启动 REPL
+///
+/// 符号表、常量池和全局变量跨行持久，因此上一行的 `let x = 5` 在下一行
+/// 的 `x + 1` 里仍然可见；用 rustyline 取代裸的 `read_line`，获得历史
+/// 记录、方向键回溯，并在 `{`/`(` 未闭合时自动续行而不是报语法错误。
 pub fn start() {
-    loop {
-        print!("{}", PROMPT);
-        io::stdout().flush().unwrap();
-        let reader = io::stdin();
-        let mut input: String = String::new();
+    let interrupt = Arc::new(AtomicBool::new(false));
+    {
+        let interrupt = interrupt.clone();
+        ctrlc::set_handler(move || {
+            interrupt.store(true, Ordering::Relaxed);
+        })
+        .expect("failed to install Ctrl-C handler");
+    }
+
+    let mut editor = DefaultEditor::new().expect("failed to start line editor");
+    let _ = editor.load_history(HISTORY_FILE);
 
-        let i = reader.read_line(&mut input).unwrap();
+    let symbol_table = Rc::new(RefCell::new(SymbolTable::new()));
+    let mut constants = vec![];
+    let globals = Rc::new(RefCell::new(vec![]));
 
-        if i == 0 || input == "exit\n" {
-            println!("Bye!");
-            return;
+    while let Some(input) = read_statement(&mut editor) {
+        if input.trim().is_empty() {
+            continue;
+        }
+        if input.trim() == "exit" {
+            break;
         }
+        let _ = editor.add_history_entry(input.as_str());
 
         let mut parser = Parser::from(input);
         let program = parser.parse_program();
-        let statements = program.statements;
-        for statement in statements {
-            println!("{:?}", statement);
+        if !parser.errors().is_empty() {
+            for err in parser.errors() {
+                eprintln!("{}", err);
+            }
+            continue;
+        }
+
+        let mut compiler = Compiler::new_with_state(symbol_table.clone(), constants.clone());
+        if let Err(err) = compiler.compile_program(&program) {
+            eprintln!("compile error: {}", err);
+            continue;
+        }
+        constants = compiler.constants();
+
+        let mut vm = Vm::new_with_globals(compiler.bytecode(), globals.clone(), interrupt.clone());
+        if let Err(err) = vm.run() {
+            eprintln!("{}", err);
+            continue;
+        }
+
+        if let Ok(obj) = vm.last_popped_stack_element() {
+            if *obj != Object::Null {
+                println!("{}", obj);
+            }
+        }
+    }
+
+    println!("Bye!");
+    let _ = editor.save_history(HISTORY_FILE);
+}
+
+/// # 读取一条（可能跨多行输入）的完整语句
+///
+/// 统计每一行里 `{`/`(` 与 `}`/`)` 的差值，只要差值大于 0 就继续以续行
+/// 提示符读取下一行，而不是把半截语句丢给解析器报错。
+fn read_statement(editor: &mut DefaultEditor) -> Option<String> {
+    let mut buffer = String::new();
+    let mut depth = 0i32;
+    loop {
+        let prompt = if buffer.is_empty() {
+            PROMPT
+        } else {
+            CONTINUE_PROMPT
+        };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                depth += brace_depth(&line);
+                if !buffer.is_empty() {
+                    buffer.push('\n');
+                }
+                buffer.push_str(&line);
+                if depth <= 0 {
+                    return Some(buffer);
+                }
+            }
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => return None,
+            Err(_) => return None,
+        }
+    }
+}
+
+fn brace_depth(line: &str) -> i32 {
+    let mut depth = 0;
+    for c in line.chars() {
+        match c {
+            '{' | '(' => depth += 1,
+            '}' | ')' => depth -= 1,
+            _ => {}
         }
     }
+    depth
 }
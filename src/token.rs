@@ -0,0 +1,82 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Illegal(String),
+    Eof,
+
+    Ident(String),
+    Int(i64),
+    Str(String),
+
+    Assign,
+    Plus,
+    Minus,
+    Bang,
+    Asterisk,
+    Slash,
+    Percent,
+    StarStar,
+    SlashSlash,
+    Amp,
+    Pipe,
+    Caret,
+    Shl,
+    Shr,
+
+    PlusAssign,
+    MinusAssign,
+    AsteriskAssign,
+    SlashAssign,
+    PercentAssign,
+
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Eq,
+    NotEq,
+
+    DotDot,
+    DotDotEq,
+
+    Comma,
+    Semicolon,
+    Colon,
+
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+
+    Function,
+    Let,
+    True,
+    False,
+    Null,
+    If,
+    Else,
+    While,
+    Return,
+    Try,
+    Catch,
+    Throw,
+}
+
+pub fn lookup_ident(ident: &str) -> Token {
+    match ident {
+        "fn" => Token::Function,
+        "let" => Token::Let,
+        "true" => Token::True,
+        "false" => Token::False,
+        "null" => Token::Null,
+        "if" => Token::If,
+        "else" => Token::Else,
+        "while" => Token::While,
+        "return" => Token::Return,
+        "try" => Token::Try,
+        "catch" => Token::Catch,
+        "throw" => Token::Throw,
+        _ => Token::Ident(ident.to_string()),
+    }
+}
@@ -0,0 +1,44 @@
+use std::rc::Rc;
+
+use crate::compiler::code::Instructions;
+use crate::object::Object;
+
+/// # try 块入口处记录的现场
+///
+/// 进入 `try` 时连同 catch 目标地址和当时的栈指针一起压入当前帧，
+/// 发生异常时据此回退栈并跳转到 catch 分支。
+#[derive(Debug, Clone)]
+pub struct TryFrame {
+    pub catch_ip: usize,
+    pub sp: usize,
+}
+
+/// # 调用帧
+///
+/// 持有被调用闭包、帧内指令指针、局部变量在值栈里的起始位置，以及这一帧
+/// 内还未退出的 try 块现场。
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub closure: Rc<Object>,
+    pub ip: usize,
+    pub base_pointer: usize,
+    pub try_frames: Vec<TryFrame>,
+}
+
+impl Frame {
+    pub fn new(closure: Rc<Object>, base_pointer: usize) -> Self {
+        Frame {
+            closure,
+            ip: 0,
+            base_pointer,
+            try_frames: vec![],
+        }
+    }
+
+    pub fn instructions(&self) -> &Instructions {
+        match self.closure.as_ref() {
+            Object::Closure(closure) => &closure.compiled_function.instructions,
+            _ => unreachable!("a frame is always built from a closure"),
+        }
+    }
+}
@@ -1,19 +1,94 @@
 use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::atomic::Ordering as AtomicOrdering;
 
 use crate::compiler::code::{Instructions, Opcode, read_operands};
 use crate::object::{HashKey, Object, RuntimeError};
 use crate::object::builtins::BUILTINS;
 use crate::vm::{FALSE, NULL, TRUE, Vm, VmResult};
-use crate::vm::frame::Frame;
+use crate::vm::frame::{Frame, TryFrame};
 
 impl Vm {
-    pub fn jump_if(&mut self, truthy: bool, ins: &Instructions, ip: usize) {
+    /// # 检查中断标志
+    ///
+    /// REPL 的 Ctrl-C 处理器会置位这个标志；跳回（循环体）和调用闭包时各检查
+    /// 一次就够了，不需要在每条指令上检查，开销可以忽略不计。用 `swap` 顺带把标志位清零，
+    /// 否则一次 Ctrl-C 会让 REPL 里后续所有循环都立刻因为"interrupted"中止。
+    pub fn check_interrupt(&self) -> VmResult<()> {
+        if self.interrupt.swap(false, AtomicOrdering::Relaxed) {
+            Err(RuntimeError::Interrupted)
+        } else {
+            Ok(())
+        }
+    }
+    pub fn jump_if(&mut self, truthy: bool, ins: &Instructions, ip: usize) -> VmResult<()> {
         if truthy {
             self.frames.last_mut().unwrap().ip += 2;
         } else {
-            self.frames.last_mut().unwrap().ip = self.read_u16(ins, ip);
+            let target = self.read_u16(ins, ip);
+            if target <= ip {
+                self.check_interrupt()?;
+            }
+            self.frames.last_mut().unwrap().ip = target;
+        }
+        Ok(())
+    }
+    /// # 执行 SetupTry，进入 try 块时记录现场
+    pub fn setup_try(&mut self, catch_ip: usize) {
+        let sp = self.sp;
+        self.frames
+            .last_mut()
+            .unwrap()
+            .try_frames
+            .push(TryFrame { catch_ip, sp });
+    }
+    /// # 执行 PopTry，正常退出 try 块时丢弃现场
+    pub fn pop_try(&mut self) {
+        self.frames.last_mut().unwrap().try_frames.pop();
+    }
+    /// # 执行 throw 语句
+    ///
+    /// 弹出栈顶值包装成 `Object::Error` 后沿 try/catch 链展开；
+    /// 找不到处理帧时转成不可恢复的运行时错误继续向外传播。
+    pub fn execute_throw(&mut self) -> VmResult<()> {
+        let value = self.pop_stack();
+        let err_obj = Rc::new(Object::Error(value));
+        if self.unwind_to_try_frame(err_obj.clone()) {
+            Ok(())
+        } else {
+            Err(RuntimeError::Uncaught(Object::clone(&err_obj)))
+        }
+    }
+    /// # 将运行时错误转化为异常对象并沿调用栈展开
+    ///
+    /// 从当前帧开始自顶向下查找最近的 `TryFrame`：找到后弹出中间的调用帧，
+    /// 把 `self.sp` 回退到保存的栈指针，将异常对象压栈，并把当前帧的
+    /// `ip` 设置为 `catch_ip`。找不到处理帧时返回 `false`，交由调用方
+    /// 按原先的方式让错误继续向外传播。
+    pub fn unwind_to_try_frame(&mut self, err_obj: Rc<Object>) -> bool {
+        while !self.frames.is_empty() {
+            if let Some(try_frame) = self.frames.last_mut().unwrap().try_frames.pop() {
+                self.sp = try_frame.sp;
+                self.push_stack(err_obj);
+                self.frames.last_mut().unwrap().ip = try_frame.catch_ip;
+                return true;
+            }
+            if self.frames.len() == 1 {
+                return false;
+            }
+            self.frames.pop();
+        }
+        false
+    }
+    /// # 将 `RuntimeError` 转化为异常对象并展开，供主循环捕获错误时调用
+    pub fn recover_or_propagate(&mut self, err: RuntimeError) -> VmResult<()> {
+        let err_obj = Rc::new(Object::Error(Rc::new(Object::String(err.to_string()))));
+        if self.unwind_to_try_frame(err_obj) {
+            Ok(())
+        } else {
+            Err(err)
         }
     }
     /// # 执行非运算
@@ -72,7 +147,7 @@ impl Vm {
                     Object::Hash(pairs) => {
                         let val = self.pop_stack();
                         let index = self.pop_stack();
-                        let key = HashKey::from_object(&*index)?;
+                        let key = HashKey::from_object(&index)?;
                         pairs.borrow_mut().insert(key, Object::clone(&val));
                     }
                     //普通赋值
@@ -88,6 +163,118 @@ impl Vm {
         }
         Ok(())
     }
+    /// # 执行复合赋值操作 (+=, -=, *=, /= ...)
+    ///
+    /// 取出目标当前值，与弹出的右值通过已有的 `execute_binary_operation`
+    /// 计算后原地写回，保留数组/Hash 的就地修改路径，但只取一次容器，
+    /// 让编译器生成一条指令而不是 load/op/store 三条。
+    pub fn execute_compound_assign_operation(
+        &mut self,
+        index: usize,
+        is_local: bool,
+        op: &Opcode,
+    ) -> VmResult<()> {
+        let opt = if is_local {
+            self.get_local(index)
+        } else {
+            self.get_global(index)
+        };
+        let obj = opt.ok_or_else(|| {
+            RuntimeError::CustomErrMsg("compound assignment to an undeclared variable".to_string())
+        })?;
+        match obj.as_ref() {
+            //数组复合赋值
+            Object::Array(items) => {
+                let rhs = self.pop_stack();
+                let idx = self.pop_stack();
+                if let Object::Integer(i) = idx.as_ref() {
+                    // Bounds-check before indexing: an out-of-range index used to
+                    // panic straight through `Vec`'s `Index`, crashing the whole
+                    // (persistent) REPL process instead of reporting an error.
+                    let len = items.borrow().len();
+                    if *i < 0 || *i as usize >= len {
+                        return Err(RuntimeError::UnSupportedIndexOperation(
+                            Object::clone(&obj),
+                            Object::clone(&idx),
+                        ));
+                    }
+                    let current = items.borrow()[*i as usize].clone();
+                    self.push_stack(Rc::new(current));
+                    self.push_stack(rhs);
+                    self.execute_binary_operation(op)?;
+                    let result = self.pop_stack();
+                    items.borrow_mut()[*i as usize] = Object::clone(&result);
+                    Ok(())
+                } else {
+                    Err(RuntimeError::UnSupportedIndexOperation(
+                        Object::clone(&obj),
+                        Object::clone(&idx),
+                    ))
+                }
+            }
+            //Hash复合赋值
+            Object::Hash(pairs) => {
+                let rhs = self.pop_stack();
+                let idx = self.pop_stack();
+                let key = HashKey::from_object(&idx)?;
+                let current = pairs.borrow().get(&key).cloned().unwrap_or(NULL);
+                self.push_stack(Rc::new(current));
+                self.push_stack(rhs);
+                self.execute_binary_operation(op)?;
+                let result = self.pop_stack();
+                pairs.borrow_mut().insert(key, Object::clone(&result));
+                Ok(())
+            }
+            //普通复合赋值
+            _ => {
+                let rhs = self.pop_stack();
+                self.push_stack(obj.clone());
+                self.push_stack(rhs);
+                self.execute_binary_operation(op)?;
+                if is_local {
+                    self.pop_and_set_local(index);
+                } else {
+                    self.pop_and_set_global(index);
+                }
+                Ok(())
+            }
+        }
+    }
+    /// # 执行 Range，按 flags 位从栈上弹出省略的端点后压入 `Object::Range`
+    ///
+    /// `flags` 的 bit0/bit1/bit2 分别表示"有起点"/"有终点"/"闭区间"，编译器
+    /// 按起点、终点的顺序压栈，所以这里按相反顺序弹出。
+    pub fn build_range(&mut self, flags: usize) -> VmResult<()> {
+        let has_start = flags & 0b001 != 0;
+        let has_end = flags & 0b010 != 0;
+        let inclusive = flags & 0b100 != 0;
+        let end = if has_end {
+            Some(self.pop_integer()?)
+        } else {
+            None
+        };
+        let start = if has_start {
+            Some(self.pop_integer()?)
+        } else {
+            None
+        };
+        self.push_stack(Rc::new(Object::Range {
+            start,
+            end,
+            inclusive,
+        }));
+        Ok(())
+    }
+    fn pop_integer(&mut self) -> VmResult<i64> {
+        let value = self.pop_stack();
+        match value.as_ref() {
+            Object::Integer(n) => Ok(*n),
+            other => Err(RuntimeError::CustomErrMsg(format!(
+                "range bound must be an integer, got {}",
+                other
+            ))),
+        }
+    }
     /// # 创建数组
     pub fn build_array(&mut self, arr_len: usize) {
         let mut arr = vec![];
@@ -127,13 +314,75 @@ impl Vm {
                     Opcode::Div => {
                         if right_val == &0 {
                             return Err(RuntimeError::ByZero(
-                                Object::clone(&left),
-                                Object::clone(&right),
+                                left.clone(),
+                                right.clone(),
                             ));
                         }
                         left_val / right_val
                     }
-                    _ => return Err(RuntimeError::UnSupportedBinOperator(op.clone())),
+                    Opcode::Mod => {
+                        if right_val == &0 {
+                            return Err(RuntimeError::ByZero(
+                                left.clone(),
+                                right.clone(),
+                            ));
+                        }
+                        left_val % right_val
+                    }
+                    Opcode::IntDiv => {
+                        if right_val == &0 {
+                            return Err(RuntimeError::ByZero(
+                                left.clone(),
+                                right.clone(),
+                            ));
+                        }
+                        let q = left_val / right_val;
+                        if left_val % right_val != 0 && (left_val < &0) != (right_val < &0) {
+                            q - 1
+                        } else {
+                            q
+                        }
+                    }
+                    Opcode::Pow => {
+                        if *right_val < 0 {
+                            return Err(RuntimeError::CustomErrMsg(
+                                "negative exponent is not supported for integers".to_string(),
+                            ));
+                        }
+                        // `right_val as u32` would silently truncate an exponent like
+                        // 2**32 down to 0, turning it into `left_val.pow(0) == 1`
+                        // instead of erroring, so reject anything that doesn't fit first.
+                        if *right_val > u32::MAX as i64 {
+                            return Err(RuntimeError::CustomErrMsg(
+                                "exponent is too large".to_string(),
+                            ));
+                        }
+                        // checked_pow catches results that overflow i64 (e.g. 2**63),
+                        // which `.pow()` would otherwise panic on and take the process down.
+                        left_val.checked_pow(*right_val as u32).ok_or_else(|| {
+                            RuntimeError::CustomErrMsg("exponentiation overflowed".to_string())
+                        })?
+                    }
+                    Opcode::Shl => {
+                        if !(0..64).contains(right_val) {
+                            return Err(RuntimeError::CustomErrMsg(
+                                "shift amount must be in 0..64".to_string(),
+                            ));
+                        }
+                        left_val << right_val
+                    }
+                    Opcode::Shr => {
+                        if !(0..64).contains(right_val) {
+                            return Err(RuntimeError::CustomErrMsg(
+                                "shift amount must be in 0..64".to_string(),
+                            ));
+                        }
+                        left_val >> right_val
+                    }
+                    Opcode::BitAnd => left_val & right_val,
+                    Opcode::BitXor => left_val ^ right_val,
+                    Opcode::BitOr => left_val | right_val,
+                    _ => return Err(RuntimeError::UnSupportedBinOperator(*op)),
                 };
                 match self.int_cache.get(r as usize) {
                     None => Rc::new(Object::Integer(r)),
@@ -145,7 +394,7 @@ impl Vm {
                     Rc::new(Object::String(left_val.clone() + right_val))
                 } else {
                     return Err(RuntimeError::UnSupportedBinOperation(
-                        op.clone(),
+                        *op,
                         left.clone(),
                         right.clone(),
                     ));
@@ -156,7 +405,7 @@ impl Vm {
                     Rc::new(Object::String(left_val.to_string() + right_val))
                 } else {
                     return Err(RuntimeError::UnSupportedBinOperation(
-                        op.clone(),
+                        *op,
                         left.clone(),
                         right.clone(),
                     ));
@@ -167,7 +416,7 @@ impl Vm {
                     Rc::new(Object::String(left_val.clone() + &right_val.to_string()))
                 } else {
                     return Err(RuntimeError::UnSupportedBinOperation(
-                        op.clone(),
+                        *op,
                         left.clone(),
                         right.clone(),
                     ));
@@ -175,7 +424,7 @@ impl Vm {
             }
             _ => {
                 return Err(RuntimeError::UnSupportedBinOperation(
-                    op.clone(),
+                    *op,
                     left.clone(),
                     right.clone(),
                 ))
@@ -187,45 +436,92 @@ impl Vm {
     /// # 执行索引操作
     pub fn execute_index_operation(&self, obj: &Object, index: &Object) -> VmResult {
         if let Object::Array(items) = obj {
-            if let Object::Integer(index) = index {
-                let value = items.borrow().get(*index as usize).cloned().unwrap_or(NULL);
-                return Ok(Rc::new(value));
+            match index {
+                Object::Integer(index) => {
+                    let value = items.borrow().get(*index as usize).cloned().unwrap_or(NULL);
+                    return Ok(Rc::new(value));
+                }
+                Object::Range {
+                    start,
+                    end,
+                    inclusive,
+                } => {
+                    let len = items.borrow().len();
+                    let (lo, hi) = Self::clamp_range(*start, *end, *inclusive, len);
+                    let slice = items.borrow()[lo..hi].to_vec();
+                    return Ok(Rc::new(Object::Array(RefCell::new(slice))));
+                }
+                _ => {}
             }
         } else if let Object::Hash(pairs) = obj {
             let key = HashKey::from_object(index)?;
             let value = pairs.borrow().get(&key).cloned().unwrap_or(NULL);
             return Ok(Rc::new(value));
+        } else if let Object::String(s) = obj {
+            if let Object::Range {
+                start,
+                end,
+                inclusive,
+            } = index
+            {
+                let len = s.chars().count();
+                let (lo, hi) = Self::clamp_range(*start, *end, *inclusive, len);
+                let slice: String = s.chars().skip(lo).take(hi - lo).collect();
+                return Ok(Rc::new(Object::String(slice)));
+            }
         }
         Err(RuntimeError::UnSupportedIndexOperation(
             obj.clone(),
             index.clone(),
         ))
     }
+    /// # 将 Range 的起止端点收敛到 `[0, len]` 范围内
+    ///
+    /// 省略的端点按 0/len 处理，负数按从末尾倒数处理；起止颠倒或越界时
+    /// 收敛为空区间而不是 panic。
+    fn clamp_range(start: Option<i64>, end: Option<i64>, inclusive: bool, len: usize) -> (usize, usize) {
+        let clamp = |v: i64| -> usize {
+            let v = if v < 0 { v + len as i64 } else { v };
+            v.clamp(0, len as i64) as usize
+        };
+        let lo = start.map(clamp).unwrap_or(0);
+        let mut hi = end.map(clamp).unwrap_or(len);
+        if inclusive {
+            hi = (hi + 1).min(len);
+        }
+        if hi < lo {
+            hi = lo;
+        }
+        (lo, hi)
+    }
     /// # 执行比较操作
     pub fn execute_comparison_operation(&mut self, op: &Opcode) -> VmResult {
         let right = self.pop_stack();
         let left = self.pop_stack();
-        if let (Object::Integer(left), Object::Integer(right)) = (left.as_ref(), right.as_ref()) {
-            let bool = match op {
-                Opcode::GreaterThan => left > right,
-                Opcode::GreaterEq => left >= right,
-                Opcode::LessThan => left < right,
-                Opcode::LessEq => left <= right,
-                Opcode::Equal => left == right,
-                Opcode::NotEqual => left != right,
-                _ => return Err(RuntimeError::UnSupportedBinOperator(op.clone())),
-            };
-            Ok(self.get_bool_from_cache(bool))
-        } else {
-            match op {
-                Opcode::Equal => Ok(self.get_bool_from_cache(left == right)),
-                Opcode::NotEqual => Ok(self.get_bool_from_cache(left != right)),
-                _ => Err(RuntimeError::UnSupportedBinOperation(
-                    op.clone(),
-                    Object::clone(&left),
-                    Object::clone(&right),
-                )),
+        // Equal/NotEqual fall back to Object's own PartialEq, so they work
+        // across any pair of types (including mixed ones); the ordered
+        // comparisons below need val_cmp to agree on a shared ordering first.
+        match op {
+            Opcode::Equal => return Ok(self.get_bool_from_cache(left == right)),
+            Opcode::NotEqual => return Ok(self.get_bool_from_cache(left != right)),
+            _ => {}
+        }
+        match val_cmp(&left, &right) {
+            Some(ordering) => {
+                let bool = match op {
+                    Opcode::GreaterThan => ordering == Ordering::Greater,
+                    Opcode::GreaterEq => ordering != Ordering::Less,
+                    Opcode::LessThan => ordering == Ordering::Less,
+                    Opcode::LessEq => ordering != Ordering::Greater,
+                    _ => return Err(RuntimeError::UnSupportedBinOperator(*op)),
+                };
+                Ok(self.get_bool_from_cache(bool))
             }
+            None => Err(RuntimeError::UnSupportedBinOperation(
+                *op,
+                Object::clone(&left),
+                Object::clone(&right),
+            )),
         }
     }
     pub fn get_bool_from_cache(&self, bool: bool) -> Rc<Object> {
@@ -248,6 +544,11 @@ impl Vm {
                         arg_nums,
                     ));
                 }
+                if self.frames.len() >= self.frame_max {
+                    return Err(RuntimeError::StackOverflow(self.frame_max));
+                }
+                // 不含循环的递归不会经过跳回，所以在调用处也检查一次中断标志。
+                self.check_interrupt()?;
                 // let num_locals = closure.compiled_function.num_locals;
                 let frame = Frame::new(callee.clone(), self.sp);
                 // Equivalent to
@@ -276,7 +577,6 @@ impl Vm {
         Ok(())
     }
     /// # 读取一个无符号整数，并返回字节长度
-
     pub fn read_usize(&self, op_code: Opcode, ip: usize) -> (usize, usize) {
         let (operands, n) = read_operands(
             &op_code.definition(),
@@ -361,7 +661,7 @@ impl Vm {
         self.frames.last_mut().unwrap().ip += n;
     }
     pub fn current_frame(&self) -> &Frame {
-        &self.frames.last().unwrap()
+        self.frames.last().unwrap()
     }
     pub fn push_frame(&mut self, frame: Frame) {
         self.frames.push(frame);
@@ -370,3 +670,25 @@ impl Vm {
         self.frames.pop().unwrap()
     }
 }
+
+/// # 比较两个值的顺序，不可比较的类型组合返回 `None`
+///
+/// 数组按字典序逐元素比较，遇到不可比较的元素时整体视为不可比较。
+fn val_cmp(left: &Object, right: &Object) -> Option<Ordering> {
+    match (left, right) {
+        (Object::Integer(a), Object::Integer(b)) => Some(a.cmp(b)),
+        (Object::String(a), Object::String(b)) => Some(a.cmp(b)),
+        (Object::Array(a), Object::Array(b)) => {
+            let a = a.borrow();
+            let b = b.borrow();
+            for (x, y) in a.iter().zip(b.iter()) {
+                match val_cmp(x, y) {
+                    Some(Ordering::Equal) => continue,
+                    other => return other,
+                }
+            }
+            Some(a.len().cmp(&b.len()))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,329 @@
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::compiler::code::Opcode;
+use crate::compiler::Bytecode;
+use crate::object::{Closure, CompiledFunction, Object, RuntimeError};
+use crate::vm::frame::Frame;
+
+pub mod frame;
+#[path = "impl.rs"]
+mod vm_impl;
+
+pub const TRUE: Object = Object::Boolean(true);
+pub const FALSE: Object = Object::Boolean(false);
+pub const NULL: Object = Object::Null;
+
+const DEFAULT_FRAME_MAX: usize = 1024;
+const INT_CACHE_SIZE: usize = 256;
+
+pub type VmResult<T = Rc<Object>> = Result<T, RuntimeError>;
+
+/// # 基于帧的栈式字节码虚拟机
+///
+/// `run` 是主调度循环：逐条取指、译码、执行，运行时错误统一通过
+/// `recover_or_propagate`（定义在 `impl.rs`）尝试交给最近的 try/catch 处理帧，
+/// 处理不了才真正向上传播给调用方。
+pub struct Vm {
+    constants: Vec<Rc<Object>>,
+    stack: Vec<Rc<Object>>,
+    sp: usize,
+    globals: Rc<RefCell<Vec<Rc<Object>>>>,
+    frames: Vec<Frame>,
+    frame_max: usize,
+    interrupt: Arc<AtomicBool>,
+    bool_cache_true: Rc<Object>,
+    bool_cache_false: Rc<Object>,
+    null_cache: Rc<Object>,
+    int_cache: Vec<Rc<Object>>,
+}
+
+impl Vm {
+    /// 便捷构造：独立全局变量表、不接收中断信号，测试里常用。
+    #[allow(dead_code)]
+    pub fn new(bytecode: Bytecode) -> Self {
+        Self::new_with_globals(
+            bytecode,
+            Rc::new(RefCell::new(vec![])),
+            Arc::new(AtomicBool::new(false)),
+        )
+    }
+
+    pub fn new_with_globals(
+        bytecode: Bytecode,
+        globals: Rc<RefCell<Vec<Rc<Object>>>>,
+        interrupt: Arc<AtomicBool>,
+    ) -> Self {
+        let main_fn = CompiledFunction {
+            instructions: bytecode.instructions,
+            num_locals: 0,
+            num_parameters: 0,
+        };
+        let main_closure = Rc::new(Object::Closure(Rc::new(Closure {
+            compiled_function: Rc::new(main_fn),
+        })));
+        let int_cache = (0..INT_CACHE_SIZE as i64)
+            .map(|n| Rc::new(Object::Integer(n)))
+            .collect();
+        Vm {
+            constants: bytecode.constants,
+            stack: vec![],
+            sp: 0,
+            globals,
+            frames: vec![Frame::new(main_closure, 0)],
+            frame_max: DEFAULT_FRAME_MAX,
+            interrupt,
+            bool_cache_true: Rc::new(TRUE),
+            bool_cache_false: Rc::new(FALSE),
+            null_cache: Rc::new(NULL),
+            int_cache,
+        }
+    }
+
+    fn is_truthy(value: &Object) -> bool {
+        !matches!(value, Object::Boolean(false) | Object::Null)
+    }
+
+    pub fn run(&mut self) -> VmResult<()> {
+        while self.current_frame().ip < self.current_frame().instructions().len() {
+            if let Err(err) = self.step() {
+                self.recover_or_propagate(err)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn step(&mut self) -> VmResult<()> {
+        let ip = self.current_frame().ip;
+        let op_byte = self.current_frame().instructions()[ip];
+        let op = Opcode::try_from(op_byte).map_err(RuntimeError::CustomErrMsg)?;
+        self.current_frame_ip_inc(1);
+
+        match op {
+            Opcode::Constant => {
+                let (idx, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                let obj = self.get_const_object(idx);
+                self.push_stack(obj);
+            }
+            Opcode::Pop => {
+                self.pop_stack();
+            }
+            Opcode::Add
+            | Opcode::Sub
+            | Opcode::Mul
+            | Opcode::Div
+            | Opcode::Mod
+            | Opcode::IntDiv
+            | Opcode::Pow
+            | Opcode::Shl
+            | Opcode::Shr
+            | Opcode::BitAnd
+            | Opcode::BitXor
+            | Opcode::BitOr => {
+                self.execute_binary_operation(&op)?;
+            }
+            Opcode::True => self.push_stack(self.bool_cache_true.clone()),
+            Opcode::False => self.push_stack(self.bool_cache_false.clone()),
+            Opcode::Null => self.push_stack(self.null_cache.clone()),
+            Opcode::Equal | Opcode::NotEqual | Opcode::GreaterThan | Opcode::GreaterEq | Opcode::LessThan | Opcode::LessEq => {
+                let result = self.execute_comparison_operation(&op)?;
+                self.push_stack(result);
+            }
+            Opcode::Minus => {
+                let value = self.pop_stack();
+                match value.as_ref() {
+                    Object::Integer(n) => {
+                        let result = Rc::new(Object::Integer(-n));
+                        self.push_stack(result);
+                    }
+                    other => {
+                        return Err(RuntimeError::UnSupportedUnOperation(
+                            Opcode::Minus,
+                            other.clone(),
+                        ))
+                    }
+                }
+            }
+            Opcode::Not => {
+                let value = self.pop_stack();
+                self.execute_not_expression(&value)?;
+            }
+            Opcode::JumpNotTruthy => {
+                let ip = self.current_frame().ip;
+                let ins = self.current_frame().instructions().clone();
+                let condition = self.pop_stack();
+                let truthy = Self::is_truthy(&condition);
+                self.jump_if(truthy, &ins, ip)?;
+            }
+            Opcode::Jump => {
+                let ip = self.current_frame().ip;
+                let ins = self.current_frame().instructions().clone();
+                let target = self.read_u16(&ins, ip);
+                if target <= ip {
+                    self.check_interrupt()?;
+                }
+                self.frames.last_mut().unwrap().ip = target;
+            }
+            Opcode::GetGlobal => {
+                let (idx, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                self.get_global_and_push(idx);
+            }
+            Opcode::SetGlobal => {
+                let (idx, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                self.pop_and_set_global(idx);
+            }
+            Opcode::GetLocal => {
+                let (idx, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                self.get_local_and_push(idx);
+            }
+            Opcode::SetLocal => {
+                let (idx, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                self.pop_and_set_local(idx);
+            }
+            Opcode::Assign => {
+                let ip = self.current_frame().ip;
+                let ins = self.current_frame().instructions().clone();
+                let (operands, n) = crate::compiler::code::read_operands(&op.definition(), &ins[ip..]);
+                self.current_frame_ip_inc(n);
+                self.execute_assign_operation_or_pop_and_set_global(operands[0], operands[1] != 0)?;
+            }
+            Opcode::CompoundAssign => {
+                let ip = self.current_frame().ip;
+                let ins = self.current_frame().instructions().clone();
+                let (operands, n) = crate::compiler::code::read_operands(&op.definition(), &ins[ip..]);
+                self.current_frame_ip_inc(n);
+                let bin_op = Opcode::try_from(operands[2] as u8).map_err(RuntimeError::CustomErrMsg)?;
+                self.execute_compound_assign_operation(operands[0], operands[1] != 0, &bin_op)?;
+            }
+            Opcode::Array => {
+                let (len, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                self.build_array(len);
+            }
+            Opcode::Hash => {
+                let (len, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                self.build_hash(len)?;
+            }
+            Opcode::Range => {
+                let (flags, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                self.build_range(flags)?;
+            }
+            Opcode::Index => {
+                let index = self.pop_stack();
+                let left = self.pop_stack();
+                let result = self.execute_index_operation(&left, &index)?;
+                self.push_stack(result);
+            }
+            Opcode::Call => {
+                let (arg_nums, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                self.call_function(arg_nums)?;
+            }
+            Opcode::ReturnValue => {
+                let return_value = self.pop_stack();
+                let frame = self.pop_frame();
+                self.sp = frame.base_pointer - 1;
+                self.push_stack(return_value);
+            }
+            Opcode::Return => {
+                let frame = self.pop_frame();
+                self.sp = frame.base_pointer - 1;
+                self.push_stack(self.null_cache.clone());
+            }
+            Opcode::GetBuiltin => {
+                let (idx, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                let builtin = self.get_builtin(idx)?;
+                self.push_stack(builtin);
+            }
+            Opcode::SetupTry => {
+                let (catch_ip, n) = self.read_usize(op, self.current_frame().ip);
+                self.current_frame_ip_inc(n);
+                self.setup_try(catch_ip);
+            }
+            Opcode::PopTry => {
+                self.pop_try();
+            }
+            Opcode::Throw => {
+                self.execute_throw()?;
+            }
+        }
+        Ok(())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::compiler::symbol_table::SymbolTable;
+    use crate::parser::Parser;
+
+    fn compile(input: &str) -> Bytecode {
+        let mut parser = Parser::from(input.to_string());
+        let program = parser.parse_program();
+        assert!(parser.errors().is_empty(), "parser errors: {:?}", parser.errors());
+        let symbol_table = Rc::new(RefCell::new(SymbolTable::new()));
+        let mut compiler = Compiler::new_with_state(symbol_table, vec![]);
+        compiler.compile_program(&program).expect("compile error");
+        compiler.bytecode()
+    }
+
+    #[test]
+    fn interrupt_flag_aborts_a_running_loop() {
+        let interrupt = Arc::new(AtomicBool::new(false));
+        let bytecode = compile("let i = 0; while (true) { i = i + 1; }");
+        let mut vm = Vm::new_with_globals(bytecode, Rc::new(RefCell::new(vec![])), interrupt.clone());
+
+        let flag = interrupt.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            flag.store(true, std::sync::atomic::Ordering::Relaxed);
+        });
+
+        let result = vm.run();
+        assert_eq!(result, Err(RuntimeError::Interrupted));
+    }
+
+    #[test]
+    fn interrupt_flag_is_cleared_after_aborting_so_later_loops_still_run() {
+        let interrupt = Arc::new(AtomicBool::new(false));
+        let globals = Rc::new(RefCell::new(vec![]));
+
+        let bytecode = compile("let i = 0; while (true) { i = i + 1; }");
+        let mut vm = Vm::new_with_globals(bytecode, globals.clone(), interrupt.clone());
+        let flag = interrupt.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            flag.store(true, std::sync::atomic::Ordering::Relaxed);
+        });
+        assert_eq!(vm.run(), Err(RuntimeError::Interrupted));
+
+        let bytecode = compile("let j = 0; while (j < 3) { j = j + 1; } j");
+        let mut vm = Vm::new_with_globals(bytecode, globals, interrupt);
+        assert!(vm.run().is_ok());
+    }
+
+    #[test]
+    fn deep_recursion_is_reported_as_stack_overflow_not_a_crash() {
+        let bytecode = compile("let f = fn(n) { f(n + 1) }; f(0);");
+        let mut vm = Vm::new(bytecode);
+        assert_eq!(vm.run(), Err(RuntimeError::StackOverflow(DEFAULT_FRAME_MAX)));
+    }
+}